@@ -0,0 +1,17 @@
+use colored::Colorize;
+
+pub fn success(msg: &str) {
+    println!("{} {}", "success:".green().bold(), msg);
+}
+
+pub fn warning(msg: &str) {
+    println!("{} {}", "warning:".yellow().bold(), msg);
+}
+
+pub fn info(msg: &str) {
+    println!("{} {}", "info:".blue().bold(), msg);
+}
+
+pub fn error(msg: &str) {
+    eprintln!("{} {}", "error:".red().bold(), msg);
+}